@@ -5,6 +5,19 @@ pub enum Operator {
     Mul,
     Div,
     Rem,
+    Pow,
+    Lt,
+    Gt,
+    Le,
+    Ge,
+    Eq,
+    NotEq,
+    And,
+    Or,
+    BitAnd,
+    BitOr,
+    Shl,
+    Shr,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -16,7 +29,6 @@ pub enum PrefixOperator {
 #[derive(Debug, Clone, PartialEq)]
 pub enum PostfixOperator {
     Factorial,
-    Exponential(u32),
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -24,6 +36,16 @@ pub enum Expr {
     PrefixExpr(PrefixOperator, Box<Expr>),
     PostfixExpr(PostfixOperator, Box<Expr>),
     UnaryExpr(i64),
+    FloatExpr(f64),
+    Ident(String),
+    Assign {
+        name: String,
+        expr: Box<Expr>,
+    },
+    Call {
+        name: String,
+        args: Vec<Expr>,
+    },
     BinaryExpr {
         left: Box<Expr>,
         right: Box<Expr>,
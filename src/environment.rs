@@ -0,0 +1,46 @@
+use crate::executor::Value;
+use std::collections::HashMap;
+
+pub struct Environment {
+    variables: HashMap<String, Value>,
+}
+
+impl Environment {
+    pub fn new() -> Self {
+        Self {
+            variables: HashMap::new(),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<Value> {
+        self.variables.get(name).copied()
+    }
+
+    pub fn set(&mut self, name: String, value: Value) {
+        self.variables.insert(name, value);
+    }
+}
+
+impl Default for Environment {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::environment::Environment;
+    use crate::executor::Value;
+
+    #[test]
+    fn environment1_test() {
+        let mut env = Environment::new();
+        assert_eq!(env.get("x"), None);
+
+        env.set("x".to_string(), Value::Int(5));
+        assert_eq!(env.get("x"), Some(Value::Int(5)));
+
+        env.set("x".to_string(), Value::Int(10));
+        assert_eq!(env.get("x"), Some(Value::Int(10)));
+    }
+}
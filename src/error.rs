@@ -0,0 +1,63 @@
+use crate::token::Token;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CalcError {
+    UnexpectedChar {
+        ch: char,
+        column: usize,
+    },
+    UnexpectedToken {
+        token: Token,
+        column: usize,
+    },
+    UnexpectedEof,
+    IntegerOverflow,
+    DivisionByZero,
+    EmptyExpression,
+    TypeError(String),
+    UndefinedVariable(String),
+    UnknownFunction(String),
+    ArityMismatch {
+        name: String,
+        expected: usize,
+        got: usize,
+    },
+    InvalidShiftAmount(i64),
+    InvalidNumber(String),
+}
+
+impl fmt::Display for CalcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CalcError::UnexpectedChar { ch, column } => {
+                write!(f, "unexpected character '{}' at column {}", ch, column)
+            }
+            CalcError::UnexpectedToken { token, column } => {
+                write!(f, "unexpected token {:?} at column {}", token, column)
+            }
+            CalcError::UnexpectedEof => write!(f, "unexpected end of input"),
+            CalcError::IntegerOverflow => write!(f, "integer overflow"),
+            CalcError::DivisionByZero => write!(f, "division by zero"),
+            CalcError::EmptyExpression => write!(f, "empty expression"),
+            CalcError::TypeError(message) => write!(f, "type error: {}", message),
+            CalcError::UndefinedVariable(name) => write!(f, "undefined variable '{}'", name),
+            CalcError::UnknownFunction(name) => write!(f, "unknown function '{}'", name),
+            CalcError::ArityMismatch {
+                name,
+                expected,
+                got,
+            } => write!(
+                f,
+                "function '{}' expects {} argument(s) but got {}",
+                name, expected, got
+            ),
+            CalcError::InvalidShiftAmount(amount) => write!(
+                f,
+                "shift amount {} is out of range (must be 0-63)",
+                amount
+            ),
+            CalcError::InvalidNumber(text) => write!(f, "invalid number literal '{}'", text),
+        }
+    }
+}
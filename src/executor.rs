@@ -1,55 +1,376 @@
 use crate::ast::{Expr, Operator, PostfixOperator, PrefixOperator};
+use crate::environment::Environment;
+use crate::error::CalcError;
+use std::fmt;
 
-pub fn eval(expr: Expr) -> Option<i64> {
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Value {
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+}
+
+impl Value {
+    fn as_numeric_f64(&self) -> Result<f64, CalcError> {
+        match self {
+            Value::Int(n) => Ok(*n as f64),
+            Value::Float(n) => Ok(*n),
+            Value::Bool(_) => Err(CalcError::TypeError(
+                "expected a number but found a boolean".to_string(),
+            )),
+        }
+    }
+
+    fn as_bool(&self) -> Result<bool, CalcError> {
+        match self {
+            Value::Bool(b) => Ok(*b),
+            _ => Err(CalcError::TypeError(
+                "expected a boolean but found a number".to_string(),
+            )),
+        }
+    }
+
+    fn as_i64(&self) -> Result<i64, CalcError> {
+        match self {
+            Value::Int(n) => Ok(*n),
+            _ => Err(CalcError::TypeError(
+                "expected an integer value".to_string(),
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Int(n) => write!(f, "{}", n),
+            Value::Float(n) => write!(f, "{}", n),
+            Value::Bool(b) => write!(f, "{}", b),
+        }
+    }
+}
+
+pub fn eval(expr: Expr, env: &mut Environment) -> Result<Value, CalcError> {
     match expr {
-        Expr::UnaryExpr(n) => Some(n),
+        Expr::UnaryExpr(n) => Ok(Value::Int(n)),
+        Expr::FloatExpr(n) => Ok(Value::Float(n)),
+        Expr::Ident(name) => env.get(&name).ok_or(CalcError::UndefinedVariable(name)),
+        Expr::Assign { name, expr } => {
+            let value = eval(*expr, env)?;
+            env.set(name, value);
+            Ok(value)
+        }
         Expr::PrefixExpr(op, expr) => {
+            let value = eval(*expr, env)?;
+
             if op == PrefixOperator::Minus {
-                let n = eval(*expr)?;
-                Some(-n)
+                match value {
+                    Value::Int(n) => Ok(Value::Int(-n)),
+                    Value::Float(n) => Ok(Value::Float(-n)),
+                    Value::Bool(_) => Err(CalcError::TypeError(
+                        "unary minus is not supported for boolean values".to_string(),
+                    )),
+                }
             } else {
-                eval(*expr)
+                Ok(value)
             }
         }
         Expr::PostfixExpr(op, expr) => match op {
-            PostfixOperator::Factorial => {
-                let n = eval(*expr)?;
-                factorial(n)
+            PostfixOperator::Factorial => match eval(*expr, env)? {
+                Value::Int(n) => factorial(n).map(Value::Int),
+                _ => Err(CalcError::TypeError(
+                    "factorial is only supported for integer values".to_string(),
+                )),
+            },
+        },
+        Expr::BinaryExpr { left, right, op } => {
+            let left = eval(*left, env)?;
+            let right = eval(*right, env)?;
+
+            eval_binary(op, left, right)
+        }
+        Expr::Call { name, args } => {
+            let args = args
+                .into_iter()
+                .map(|arg| eval(arg, env))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            call_builtin(&name, args)
+        }
+    }
+}
+
+fn call_builtin(name: &str, args: Vec<Value>) -> Result<Value, CalcError> {
+    match name {
+        "sqrt" => {
+            check_arity(name, &args, 1)?;
+            Ok(Value::Float(args[0].as_numeric_f64()?.sqrt()))
+        }
+        "abs" => {
+            check_arity(name, &args, 1)?;
+            match args[0] {
+                Value::Int(n) => n
+                    .checked_abs()
+                    .map(Value::Int)
+                    .ok_or(CalcError::IntegerOverflow),
+                Value::Float(n) => Ok(Value::Float(n.abs())),
+                Value::Bool(_) => Err(CalcError::TypeError(
+                    "abs is not supported for boolean values".to_string(),
+                )),
             }
-            PostfixOperator::Exponential(exp) => {
-                let n = eval(*expr)?;
-                n.checked_pow(exp)
+        }
+        "gcd" => {
+            check_arity(name, &args, 2)?;
+            let l = args[0].as_i64()?;
+            let r = args[1].as_i64()?;
+            i64::try_from(gcd(l, r))
+                .map(Value::Int)
+                .map_err(|_| CalcError::IntegerOverflow)
+        }
+        "min" => {
+            check_arity(name, &args, 2)?;
+            eval_min_max(&args, |l, r| l <= r)
+        }
+        "max" => {
+            check_arity(name, &args, 2)?;
+            eval_min_max(&args, |l, r| l >= r)
+        }
+        "pow" => {
+            check_arity(name, &args, 2)?;
+            match (args[0], args[1]) {
+                (Value::Int(l), Value::Int(r)) => {
+                    let exp = u32::try_from(r).map_err(|_| CalcError::IntegerOverflow)?;
+                    l.checked_pow(exp)
+                        .map(Value::Int)
+                        .ok_or(CalcError::IntegerOverflow)
+                }
+                (l, r) => {
+                    let l = l.as_numeric_f64()?;
+                    let r = r.as_numeric_f64()?;
+                    Ok(Value::Float(l.powf(r)))
+                }
             }
+        }
+        _ => Err(CalcError::UnknownFunction(name.to_string())),
+    }
+}
+
+fn check_arity(name: &str, args: &[Value], expected: usize) -> Result<(), CalcError> {
+    if args.len() == expected {
+        Ok(())
+    } else {
+        Err(CalcError::ArityMismatch {
+            name: name.to_string(),
+            expected,
+            got: args.len(),
+        })
+    }
+}
+
+fn eval_min_max(args: &[Value], pick: impl Fn(f64, f64) -> bool) -> Result<Value, CalcError> {
+    match (args[0], args[1]) {
+        (Value::Int(l), Value::Int(r)) => {
+            Ok(Value::Int(if pick(l as f64, r as f64) { l } else { r }))
+        }
+        (l, r) => {
+            let l = l.as_numeric_f64()?;
+            let r = r.as_numeric_f64()?;
+            Ok(Value::Float(if pick(l, r) { l } else { r }))
+        }
+    }
+}
+
+fn gcd(a: i64, b: i64) -> u64 {
+    let (mut a, mut b) = (a.unsigned_abs(), b.unsigned_abs());
+
+    while b != 0 {
+        let t = b;
+        b = a % b;
+        a = t;
+    }
+
+    a
+}
+
+fn eval_binary(op: Operator, left: Value, right: Value) -> Result<Value, CalcError> {
+    match op {
+        Operator::Add
+        | Operator::Sub
+        | Operator::Mul
+        | Operator::Div
+        | Operator::Rem
+        | Operator::Pow => eval_arithmetic(op, left, right),
+        Operator::Lt
+        | Operator::Gt
+        | Operator::Le
+        | Operator::Ge
+        | Operator::Eq
+        | Operator::NotEq => eval_comparison(op, left, right),
+        Operator::And => eval_logical(left, right, |l, r| l && r),
+        Operator::Or => eval_logical(left, right, |l, r| l || r),
+        Operator::BitAnd => eval_bitwise(left, right, |l, r| l & r),
+        Operator::BitOr => eval_bitwise(left, right, |l, r| l | r),
+        Operator::Shl => eval_shift(left, right, i64::wrapping_shl),
+        Operator::Shr => eval_shift(left, right, i64::wrapping_shr),
+    }
+}
+
+fn eval_arithmetic(op: Operator, left: Value, right: Value) -> Result<Value, CalcError> {
+    match (left, right) {
+        (Value::Int(l), Value::Int(r)) => match op {
+            Operator::Add => l
+                .checked_add(r)
+                .map(Value::Int)
+                .ok_or(CalcError::IntegerOverflow),
+            Operator::Sub => l
+                .checked_sub(r)
+                .map(Value::Int)
+                .ok_or(CalcError::IntegerOverflow),
+            Operator::Mul => l
+                .checked_mul(r)
+                .map(Value::Int)
+                .ok_or(CalcError::IntegerOverflow),
+            Operator::Rem => {
+                if r == 0 {
+                    Err(CalcError::DivisionByZero)
+                } else {
+                    l.checked_rem(r)
+                        .map(Value::Int)
+                        .ok_or(CalcError::IntegerOverflow)
+                }
+            }
+            Operator::Div => {
+                if r == 0 {
+                    Err(CalcError::DivisionByZero)
+                } else if l % r == 0 {
+                    l.checked_div(r)
+                        .map(Value::Int)
+                        .ok_or(CalcError::IntegerOverflow)
+                } else {
+                    Ok(Value::Float(l as f64 / r as f64))
+                }
+            }
+            Operator::Pow => {
+                let exp = u32::try_from(r).map_err(|_| CalcError::IntegerOverflow)?;
+                l.checked_pow(exp)
+                    .map(Value::Int)
+                    .ok_or(CalcError::IntegerOverflow)
+            }
+            _ => unreachable!("eval_binary only routes arithmetic operators here"),
         },
-        Expr::BinaryExpr { left, right, op } => {
-            let left = eval(*left)?;
-            let right = eval(*right)?;
+        (Value::Bool(_), _) | (_, Value::Bool(_)) => Err(CalcError::TypeError(
+            "arithmetic operators require numeric operands".to_string(),
+        )),
+        (l, r) => {
+            let l = l.as_numeric_f64()?;
+            let r = r.as_numeric_f64()?;
 
             match op {
-                Operator::Add => left.checked_add(right),
-                Operator::Sub => left.checked_sub(right),
-                Operator::Div => left.checked_div(right),
-                Operator::Mul => left.checked_mul(right),
-                Operator::Rem => left.checked_rem(right),
+                Operator::Add => Ok(Value::Float(l + r)),
+                Operator::Sub => Ok(Value::Float(l - r)),
+                Operator::Mul => Ok(Value::Float(l * r)),
+                Operator::Div => {
+                    if r == 0.0 {
+                        Err(CalcError::DivisionByZero)
+                    } else {
+                        Ok(Value::Float(l / r))
+                    }
+                }
+                Operator::Rem => {
+                    if r == 0.0 {
+                        Err(CalcError::DivisionByZero)
+                    } else {
+                        Ok(Value::Float(l % r))
+                    }
+                }
+                Operator::Pow => Ok(Value::Float(l.powf(r))),
+                _ => unreachable!("eval_binary only routes arithmetic operators here"),
             }
         }
     }
 }
 
-fn factorial(n: i64) -> Option<i64> {
+fn eval_comparison(op: Operator, left: Value, right: Value) -> Result<Value, CalcError> {
+    match (left, right) {
+        (Value::Int(l), Value::Int(r)) => Ok(Value::Bool(compare(op, l, r)?)),
+        (Value::Bool(l), Value::Bool(r)) => match op {
+            Operator::Eq => Ok(Value::Bool(l == r)),
+            Operator::NotEq => Ok(Value::Bool(l != r)),
+            _ => Err(CalcError::TypeError(
+                "boolean values only support == and !=".to_string(),
+            )),
+        },
+        (l, r) => {
+            let l = l.as_numeric_f64()?;
+            let r = r.as_numeric_f64()?;
+
+            Ok(Value::Bool(compare(op, l, r)?))
+        }
+    }
+}
+
+fn compare<T: PartialOrd>(op: Operator, l: T, r: T) -> Result<bool, CalcError> {
+    match op {
+        Operator::Lt => Ok(l < r),
+        Operator::Gt => Ok(l > r),
+        Operator::Le => Ok(l <= r),
+        Operator::Ge => Ok(l >= r),
+        Operator::Eq => Ok(l == r),
+        Operator::NotEq => Ok(l != r),
+        _ => unreachable!("eval_comparison only routes comparison operators here"),
+    }
+}
+
+fn eval_logical(
+    left: Value,
+    right: Value,
+    f: impl Fn(bool, bool) -> bool,
+) -> Result<Value, CalcError> {
+    let l = left.as_bool()?;
+    let r = right.as_bool()?;
+
+    Ok(Value::Bool(f(l, r)))
+}
+
+fn eval_bitwise(
+    left: Value,
+    right: Value,
+    f: impl Fn(i64, i64) -> i64,
+) -> Result<Value, CalcError> {
+    let l = left.as_i64()?;
+    let r = right.as_i64()?;
+
+    Ok(Value::Int(f(l, r)))
+}
+
+fn eval_shift(left: Value, right: Value, f: impl Fn(i64, u32) -> i64) -> Result<Value, CalcError> {
+    let l = left.as_i64()?;
+    let r = right.as_i64()?;
+
+    let amount = u32::try_from(r)
+        .ok()
+        .filter(|&amount| amount < 64)
+        .ok_or(CalcError::InvalidShiftAmount(r))?;
+
+    Ok(Value::Int(f(l, amount)))
+}
+
+fn factorial(n: i64) -> Result<i64, CalcError> {
     let mut sum: i64 = 1;
 
     for i in 2..=n {
-        sum = sum.checked_mul(i)?
+        sum = sum.checked_mul(i).ok_or(CalcError::IntegerOverflow)?;
     }
 
-    Some(sum)
+    Ok(sum)
 }
 
 #[cfg(test)]
 mod tests {
     use crate::ast::Expr;
-    use crate::executor::eval;
+    use crate::environment::Environment;
+    use crate::error::CalcError;
+    use crate::executor::{eval, Value};
     use crate::lexer::Lexer;
     use crate::parser::Parser;
 
@@ -61,45 +382,259 @@ mod tests {
         parser.parse().unwrap()
     }
 
+    fn eval_line(line: &str) -> Result<Value, CalcError> {
+        let mut env = Environment::new();
+        eval(create_expr(line), &mut env)
+    }
+
     #[test]
     fn eval1_test() {
-        let expr = create_expr("5 + 3 * 6 - 3");
-        assert_eq!(eval(expr).unwrap(), 20);
+        assert_eq!(eval_line("5 + 3 * 6 - 3").unwrap(), Value::Int(20));
     }
 
     #[test]
     fn eval2_test() {
-        let expr = create_expr("-(3 * 2)! / (11 % 3)");
-        assert_eq!(eval(expr).unwrap(), -360);
+        assert_eq!(eval_line("-(3 * 2)! / (11 % 3)").unwrap(), Value::Int(-360));
     }
 
     #[test]
     fn eval3_test() {
-        let expr = create_expr("1! + 0!");
-        assert_eq!(eval(expr).unwrap(), 2);
+        assert_eq!(eval_line("1! + 0!").unwrap(), Value::Int(2));
     }
 
     #[test]
     fn eval4_test() {
-        let expr = create_expr("1! + 0!");
-        assert_eq!(eval(expr).unwrap(), 2);
+        assert_eq!(eval_line("1! + 0!").unwrap(), Value::Int(2));
     }
 
     #[test]
     fn eval5_test() {
-        let expr = create_expr("2^3 + 10");
-        assert_eq!(eval(expr).unwrap(), 18);
+        assert_eq!(eval_line("2^3 + 10").unwrap(), Value::Int(18));
     }
 
     #[test]
     fn eval6_test() {
-        let expr = create_expr("1 % 0");
-        assert!(eval(expr).is_none());
+        assert_eq!(eval_line("1 % 0").unwrap_err(), CalcError::DivisionByZero);
     }
 
     #[test]
     fn eval7_test() {
-        let expr = create_expr("9223372036854775807 + 1");
-        assert!(eval(expr).is_none());
+        assert_eq!(
+            eval_line("9223372036854775807 + 1").unwrap_err(),
+            CalcError::IntegerOverflow
+        );
+    }
+
+    #[test]
+    fn eval8_test() {
+        assert_eq!(eval_line("3.5 * 2").unwrap(), Value::Float(7.0));
+    }
+
+    #[test]
+    fn eval9_test() {
+        assert_eq!(eval_line("1 / 3").unwrap(), Value::Float(1.0 / 3.0));
+    }
+
+    #[test]
+    fn eval10_test() {
+        assert_eq!(eval_line("4 / 2").unwrap(), Value::Int(2));
+    }
+
+    #[test]
+    fn eval11_test() {
+        assert_eq!(eval_line("2.0e3 / 1000").unwrap(), Value::Float(2.0));
+    }
+
+    #[test]
+    fn eval12_test() {
+        assert!(matches!(
+            eval_line("2.5!").unwrap_err(),
+            CalcError::TypeError(_)
+        ));
+    }
+
+    #[test]
+    fn eval13_test() {
+        assert_eq!(eval_line("2^3^2").unwrap(), Value::Int(512));
+    }
+
+    #[test]
+    fn eval14_test() {
+        assert_eq!(eval_line("2^(1+1)").unwrap(), Value::Int(4));
+    }
+
+    #[test]
+    fn eval15_test() {
+        assert_eq!(eval_line("2^-1").unwrap_err(), CalcError::IntegerOverflow);
+    }
+
+    #[test]
+    fn eval16_test() {
+        assert_eq!(eval_line("3 < 5").unwrap(), Value::Bool(true));
+    }
+
+    #[test]
+    fn eval17_test() {
+        assert_eq!(eval_line("2 + 2 == 4").unwrap(), Value::Bool(true));
+    }
+
+    #[test]
+    fn eval18_test() {
+        assert_eq!(eval_line("1 != 0").unwrap(), Value::Bool(true));
+    }
+
+    #[test]
+    fn eval19_test() {
+        assert_eq!(eval_line("1 < 2 && 3 < 2").unwrap(), Value::Bool(false));
+    }
+
+    #[test]
+    fn eval20_test() {
+        assert_eq!(eval_line("1 < 2 || 3 < 2").unwrap(), Value::Bool(true));
+    }
+
+    #[test]
+    fn eval21_test() {
+        assert!(matches!(
+            eval_line("(1 < 2) + 1").unwrap_err(),
+            CalcError::TypeError(_)
+        ));
+    }
+
+    #[test]
+    fn eval22_test() {
+        let mut env = Environment::new();
+
+        assert_eq!(eval(create_expr("x = 5"), &mut env).unwrap(), Value::Int(5));
+        assert_eq!(
+            eval(create_expr("x * 2"), &mut env).unwrap(),
+            Value::Int(10)
+        );
+    }
+
+    #[test]
+    fn eval23_test() {
+        assert_eq!(
+            eval_line("y").unwrap_err(),
+            CalcError::UndefinedVariable("y".to_string())
+        );
+    }
+
+    #[test]
+    fn eval24_test() {
+        assert_eq!(eval_line("sqrt(9)").unwrap(), Value::Float(3.0));
+    }
+
+    #[test]
+    fn eval25_test() {
+        assert_eq!(eval_line("gcd(12, 18)").unwrap(), Value::Int(6));
+    }
+
+    #[test]
+    fn eval26_test() {
+        assert_eq!(eval_line("abs(-4)").unwrap(), Value::Int(4));
+    }
+
+    #[test]
+    fn eval27_test() {
+        assert_eq!(eval_line("min(3, 7)").unwrap(), Value::Int(3));
+        assert_eq!(eval_line("max(3, 7)").unwrap(), Value::Int(7));
+    }
+
+    #[test]
+    fn eval28_test() {
+        assert_eq!(eval_line("pow(2, 10)").unwrap(), Value::Int(1024));
+    }
+
+    #[test]
+    fn eval29_test() {
+        assert_eq!(
+            eval_line("nope(1)").unwrap_err(),
+            CalcError::UnknownFunction("nope".to_string())
+        );
+    }
+
+    #[test]
+    fn eval30_test() {
+        assert_eq!(
+            eval_line("sqrt(1, 2)").unwrap_err(),
+            CalcError::ArityMismatch {
+                name: "sqrt".to_string(),
+                expected: 1,
+                got: 2
+            }
+        );
+    }
+
+    #[test]
+    fn eval31_test() {
+        assert_eq!(eval_line("5 & 3").unwrap(), Value::Int(1));
+        assert_eq!(eval_line("5 | 2").unwrap(), Value::Int(7));
+    }
+
+    #[test]
+    fn eval32_test() {
+        assert_eq!(eval_line("1 << 4").unwrap(), Value::Int(16));
+        assert_eq!(eval_line("256 >> 4").unwrap(), Value::Int(16));
+    }
+
+    #[test]
+    fn eval33_test() {
+        assert_eq!(
+            eval_line("1 << 64").unwrap_err(),
+            CalcError::InvalidShiftAmount(64)
+        );
+    }
+
+    #[test]
+    fn eval34_test() {
+        assert!(matches!(
+            eval_line("1.5 & 1").unwrap_err(),
+            CalcError::TypeError(_)
+        ));
+        assert!(matches!(
+            eval_line("(1 < 2) | 1").unwrap_err(),
+            CalcError::TypeError(_)
+        ));
+    }
+
+    #[test]
+    fn eval35_test() {
+        assert_eq!(eval_line("8 | 5 & 3").unwrap(), Value::Int(9));
+    }
+
+    #[test]
+    fn eval36_test() {
+        assert_eq!(
+            eval_line("abs(1 << 63)").unwrap_err(),
+            CalcError::IntegerOverflow
+        );
+    }
+
+    #[test]
+    fn eval37_test() {
+        assert_eq!(
+            eval_line("gcd(1 << 63, 0)").unwrap_err(),
+            CalcError::IntegerOverflow
+        );
+    }
+
+    #[test]
+    fn eval38_test() {
+        assert_eq!(
+            eval_line("9007199254740993 == 9007199254740992").unwrap(),
+            Value::Bool(false)
+        );
+    }
+
+    #[test]
+    fn eval39_test() {
+        assert_eq!(eval_line("(1 < 2) == (3 < 4)").unwrap(), Value::Bool(true));
+        assert_eq!(eval_line("(1 < 2) != (3 < 2)").unwrap(), Value::Bool(true));
+    }
+
+    #[test]
+    fn eval40_test() {
+        assert_eq!(eval_line("3.5^2").unwrap(), Value::Float(12.25));
     }
 }
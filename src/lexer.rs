@@ -1,89 +1,208 @@
-use crate::token::Token;
+use crate::error::CalcError;
+use crate::token::{Spanned, Token};
 
 pub struct Lexer<'a> {
     input: std::iter::Peekable<std::str::Chars<'a>>,
+    column: usize,
 }
 
 impl<'a> Lexer<'a> {
     pub fn new(input: &'a str) -> Self {
         Self {
             input: input.chars().peekable(),
+            column: 1,
         }
     }
 
-    pub fn tokenize(&mut self) -> Option<Vec<Token>> {
-        let mut tokens = Vec::<Token>::new();
+    pub fn tokenize(&mut self) -> Result<Vec<Spanned<Token>>, CalcError> {
+        let mut tokens = Vec::<Spanned<Token>>::new();
 
-        while let Some(ch) = self.input.peek() {
+        while let Some(&ch) = self.input.peek() {
             if ch.is_whitespace() {
-                self.input.next();
+                self.advance();
                 continue;
             }
 
+            let column = self.column;
+
             let token = match ch {
                 '+' => {
-                    self.input.next();
+                    self.advance();
                     Token::Plus
                 }
                 '-' => {
-                    self.input.next();
+                    self.advance();
                     Token::Minus
                 }
                 '*' => {
-                    self.input.next();
+                    self.advance();
                     Token::Asterisk
                 }
                 '/' => {
-                    self.input.next();
+                    self.advance();
                     Token::Slash
                 }
                 '%' => {
-                    self.input.next();
+                    self.advance();
                     Token::Percent
                 }
                 '(' => {
-                    self.input.next();
+                    self.advance();
                     Token::Lparen
                 }
                 ')' => {
-                    self.input.next();
+                    self.advance();
                     Token::Rparen
                 }
                 '!' => {
-                    self.input.next();
-                    Token::Exclamation
+                    self.advance();
+                    self.consume_if_eq_follows(Token::NotEq, Token::Exclamation)
                 }
                 '^' => {
-                    self.input.next();
+                    self.advance();
                     Token::Circumflex
                 }
+                ',' => {
+                    self.advance();
+                    Token::Comma
+                }
+                '=' => {
+                    self.advance();
+                    self.consume_if_eq_follows(Token::EqEq, Token::Assign)
+                }
+                '<' => {
+                    self.advance();
+
+                    if self.input.peek() == Some(&'<') {
+                        self.advance();
+                        Token::Shl
+                    } else {
+                        self.consume_if_eq_follows(Token::Le, Token::Lt)
+                    }
+                }
+                '>' => {
+                    self.advance();
+
+                    if self.input.peek() == Some(&'>') {
+                        self.advance();
+                        Token::Shr
+                    } else {
+                        self.consume_if_eq_follows(Token::Ge, Token::Gt)
+                    }
+                }
+                '&' => {
+                    self.advance();
+
+                    if self.input.peek() == Some(&'&') {
+                        self.advance();
+                        Token::AmperAmper
+                    } else {
+                        Token::Amper
+                    }
+                }
+                '|' => {
+                    self.advance();
+
+                    if self.input.peek() == Some(&'|') {
+                        self.advance();
+                        Token::PipePipe
+                    } else {
+                        Token::Pipe
+                    }
+                }
                 '0'..='9' => self.consume_num()?,
-                _ => return None,
+                ch if ch.is_alphabetic() || ch == '_' => self.consume_ident(),
+                _ => return Err(CalcError::UnexpectedChar { ch, column }),
             };
 
-            tokens.push(token);
+            tokens.push(Spanned {
+                value: token,
+                column,
+            });
+        }
+
+        Ok(tokens)
+    }
+
+    fn advance(&mut self) -> Option<char> {
+        let ch = self.input.next();
+
+        if ch.is_some() {
+            self.column += 1;
+        }
+
+        ch
+    }
+
+    fn consume_num(&mut self) -> Result<Token, CalcError> {
+        let mut buf = String::new();
+        let mut is_float = false;
+
+        self.consume_digits(&mut buf);
+
+        if self.input.peek() == Some(&'.') {
+            is_float = true;
+            buf.push('.');
+            self.advance();
+            self.consume_digits(&mut buf);
+        }
+
+        if matches!(self.input.peek(), Some('e') | Some('E')) {
+            is_float = true;
+            buf.push(self.advance().unwrap());
+
+            if matches!(self.input.peek(), Some('+') | Some('-')) {
+                buf.push(self.advance().unwrap());
+            }
+
+            self.consume_digits(&mut buf);
         }
 
-        Some(tokens)
+        if is_float {
+            buf.parse::<f64>()
+                .map(Token::Float)
+                .map_err(|_| CalcError::InvalidNumber(buf.clone()))
+        } else {
+            buf.parse::<i64>()
+                .map(Token::Num)
+                .map_err(|_| CalcError::IntegerOverflow)
+        }
     }
 
-    fn consume_num(&mut self) -> Option<Token> {
-        let mut sum: i64 = 0;
+    fn consume_ident(&mut self) -> Token {
+        let mut buf = String::new();
 
-        while let Some(ch) = self.input.peek() {
+        while let Some(&ch) = self.input.peek() {
+            if ch.is_alphanumeric() || ch == '_' {
+                buf.push(ch);
+                self.advance();
+            } else {
+                break;
+            }
+        }
+
+        Token::Ident(buf)
+    }
+
+    fn consume_if_eq_follows(&mut self, two_char: Token, one_char: Token) -> Token {
+        if self.input.peek() == Some(&'=') {
+            self.advance();
+            two_char
+        } else {
+            one_char
+        }
+    }
+
+    fn consume_digits(&mut self, buf: &mut String) {
+        while let Some(&ch) = self.input.peek() {
             match ch {
                 '0'..='9' => {
-                    let temp = sum.checked_mul(10)?;
-                    let num = ch.to_digit(10).unwrap_or_else(|| 0) as i64;
-                    sum = temp.checked_add(num)?;
-
-                    self.input.next();
+                    buf.push(ch);
+                    self.advance();
                 }
-                _ => return Some(Token::Num(sum)),
+                _ => break,
             }
         }
-
-        Some(Token::Num(sum))
     }
 }
 
@@ -97,28 +216,119 @@ mod tests {
         let mut lexer = Lexer::new("1 + 2 - (3 * 4 / 5)! % 6^2");
         let tokens = &lexer.tokenize().unwrap()[..];
 
-        assert_eq!(tokens[0], Token::Num(1));
-        assert_eq!(tokens[1], Token::Plus);
-        assert_eq!(tokens[2], Token::Num(2));
-        assert_eq!(tokens[3], Token::Minus);
-        assert_eq!(tokens[4], Token::Lparen);
-        assert_eq!(tokens[5], Token::Num(3));
-        assert_eq!(tokens[6], Token::Asterisk);
-        assert_eq!(tokens[7], Token::Num(4));
-        assert_eq!(tokens[8], Token::Slash);
-        assert_eq!(tokens[9], Token::Num(5));
-        assert_eq!(tokens[10], Token::Rparen);
-        assert_eq!(tokens[11], Token::Exclamation);
-        assert_eq!(tokens[12], Token::Percent);
-        assert_eq!(tokens[13], Token::Num(6));
-        assert_eq!(tokens[14], Token::Circumflex);
-        assert_eq!(tokens[15], Token::Num(2));
+        assert_eq!(tokens[0].value, Token::Num(1));
+        assert_eq!(tokens[1].value, Token::Plus);
+        assert_eq!(tokens[2].value, Token::Num(2));
+        assert_eq!(tokens[3].value, Token::Minus);
+        assert_eq!(tokens[4].value, Token::Lparen);
+        assert_eq!(tokens[5].value, Token::Num(3));
+        assert_eq!(tokens[6].value, Token::Asterisk);
+        assert_eq!(tokens[7].value, Token::Num(4));
+        assert_eq!(tokens[8].value, Token::Slash);
+        assert_eq!(tokens[9].value, Token::Num(5));
+        assert_eq!(tokens[10].value, Token::Rparen);
+        assert_eq!(tokens[11].value, Token::Exclamation);
+        assert_eq!(tokens[12].value, Token::Percent);
+        assert_eq!(tokens[13].value, Token::Num(6));
+        assert_eq!(tokens[14].value, Token::Circumflex);
+        assert_eq!(tokens[15].value, Token::Num(2));
     }
 
     #[test]
     fn lexer2_test() {
         let mut lexer = Lexer::new("9223372036854775808");
-        let tokens = &lexer.tokenize();
-        assert!(tokens.is_none());
+        let tokens = lexer.tokenize();
+        assert!(tokens.is_err());
+    }
+
+    #[test]
+    fn lexer3_test() {
+        let mut lexer = Lexer::new("3.5 * 2.0e3");
+        let tokens = &lexer.tokenize().unwrap()[..];
+
+        assert_eq!(tokens[0].value, Token::Float(3.5));
+        assert_eq!(tokens[1].value, Token::Asterisk);
+        assert_eq!(tokens[2].value, Token::Float(2000.0));
+    }
+
+    #[test]
+    fn lexer4_test() {
+        let mut lexer = Lexer::new("1 + @");
+        let err = lexer.tokenize().unwrap_err();
+
+        assert_eq!(
+            err,
+            crate::error::CalcError::UnexpectedChar { ch: '@', column: 5 }
+        );
+    }
+
+    #[test]
+    fn lexer5_test() {
+        let mut lexer = Lexer::new("3 < 5 >= 2 == 4 != 1 <= 9 > 0 && 1 || 0");
+        let tokens = &lexer.tokenize().unwrap()[..];
+
+        assert_eq!(tokens[1].value, Token::Lt);
+        assert_eq!(tokens[3].value, Token::Ge);
+        assert_eq!(tokens[5].value, Token::EqEq);
+        assert_eq!(tokens[7].value, Token::NotEq);
+        assert_eq!(tokens[9].value, Token::Le);
+        assert_eq!(tokens[11].value, Token::Gt);
+        assert_eq!(tokens[13].value, Token::AmperAmper);
+        assert_eq!(tokens[15].value, Token::PipePipe);
+    }
+
+    #[test]
+    fn lexer6_test() {
+        let mut lexer = Lexer::new("1 = 2");
+        let tokens = &lexer.tokenize().unwrap()[..];
+
+        assert_eq!(tokens[1].value, Token::Assign);
+    }
+
+    #[test]
+    fn lexer7_test() {
+        let mut lexer = Lexer::new("x = ans_1 + 2");
+        let tokens = &lexer.tokenize().unwrap()[..];
+
+        assert_eq!(tokens[0].value, Token::Ident("x".to_string()));
+        assert_eq!(tokens[1].value, Token::Assign);
+        assert_eq!(tokens[2].value, Token::Ident("ans_1".to_string()));
+        assert_eq!(tokens[3].value, Token::Plus);
+        assert_eq!(tokens[4].value, Token::Num(2));
+    }
+
+    #[test]
+    fn lexer8_test() {
+        let mut lexer = Lexer::new("gcd(12, 18)");
+        let tokens = &lexer.tokenize().unwrap()[..];
+
+        assert_eq!(tokens[0].value, Token::Ident("gcd".to_string()));
+        assert_eq!(tokens[1].value, Token::Lparen);
+        assert_eq!(tokens[2].value, Token::Num(12));
+        assert_eq!(tokens[3].value, Token::Comma);
+        assert_eq!(tokens[4].value, Token::Num(18));
+        assert_eq!(tokens[5].value, Token::Rparen);
+    }
+
+    #[test]
+    fn lexer9_test() {
+        let mut lexer = Lexer::new("3 & 5 | 2 << 1 >> 4");
+        let tokens = &lexer.tokenize().unwrap()[..];
+
+        assert_eq!(tokens[1].value, Token::Amper);
+        assert_eq!(tokens[3].value, Token::Pipe);
+        assert_eq!(tokens[5].value, Token::Shl);
+        assert_eq!(tokens[7].value, Token::Shr);
+    }
+
+    #[test]
+    fn lexer10_test() {
+        let mut lexer = Lexer::new("1e");
+        let err = lexer.tokenize().unwrap_err();
+
+        assert_eq!(
+            err,
+            crate::error::CalcError::InvalidNumber("1e".to_string())
+        );
     }
 }
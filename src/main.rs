@@ -1,32 +1,56 @@
 mod ast;
+mod environment;
+mod error;
 mod executor;
 mod lexer;
 mod parser;
 mod token;
 
+use crate::environment::Environment;
+use crate::error::CalcError;
+use crate::executor::Value;
 use crate::lexer::Lexer;
 use crate::parser::Parser;
 use std::io::{self};
 
 fn main() {
+    let mut env = Environment::new();
+
     loop {
         let mut line = String::new();
-        io::stdin().read_line(&mut line);
 
-        if line.starts_with("q") {
-            break;
+        match io::stdin().read_line(&mut line) {
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(_) => break,
         }
 
-        let mut lexer = Lexer::new(&line);
+        let trimmed = line.trim();
+
+        if trimmed == "q" || trimmed == "quit" {
+            break;
+        }
 
-        if let Some(tokens) = lexer.tokenize() {
-            let mut parser = Parser::new(tokens);
+        if trimmed.is_empty() {
+            continue;
+        }
 
-            if let Some(expr) = parser.parse() {
-                if let Some(n) = executor::eval(expr) {
-                    println!("{}", n);
-                }
+        match run(&line, &mut env) {
+            Ok(value) => {
+                env.set("ans".to_string(), value);
+                println!("{}", value);
             }
+            Err(err) => println!("error: {}", err),
         }
     }
 }
+
+fn run(line: &str, env: &mut Environment) -> Result<Value, CalcError> {
+    let mut lexer = Lexer::new(line);
+    let tokens = lexer.tokenize()?;
+
+    let mut parser = Parser::new(tokens);
+    let expr = parser.parse()?;
+
+    executor::eval(expr, env)
+}
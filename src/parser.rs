@@ -1,29 +1,49 @@
 use crate::ast::{Expr, Operator, PostfixOperator, PrefixOperator};
-use crate::token::Token;
+use crate::error::CalcError;
+use crate::token::{Spanned, Token};
 
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 enum Precedence {
     Lowest,
+    Logical,
+    BitOr,
+    BitAnd,
+    Comparison,
+    Shift,
     Sum,
     Product,
+    Exponent,
     Prefix,
 }
 
 pub struct Parser {
-    tokens: Vec<Token>,
+    tokens: Vec<Spanned<Token>>,
     index: usize,
 }
 
 impl Parser {
-    pub fn new(tokens: Vec<Token>) -> Self {
+    pub fn new(tokens: Vec<Spanned<Token>>) -> Self {
         Self { tokens, index: 0 }
     }
 
-    pub fn parse(&mut self) -> Option<Expr> {
-        self.parse_expr(Precedence::Lowest)
+    pub fn parse(&mut self) -> Result<Expr, CalcError> {
+        if self.tokens.is_empty() {
+            return Err(CalcError::EmptyExpression);
+        }
+
+        let expr = self.parse_expr(Precedence::Lowest)?;
+
+        if let Some(spanned) = self.peek_token() {
+            return Err(CalcError::UnexpectedToken {
+                token: spanned.value.clone(),
+                column: spanned.column,
+            });
+        }
+
+        Ok(expr)
     }
 
-    fn parse_expr(&mut self, p: Precedence) -> Option<Expr> {
+    fn parse_expr(&mut self, p: Precedence) -> Result<Expr, CalcError> {
         let mut lhs = self.parse_prefix_expr()?;
         lhs = self.parse_postfix_expr(lhs)?;
 
@@ -32,120 +52,242 @@ impl Parser {
             lhs = self.parse_infix_expr(lhs)?;
         }
 
-        Some(lhs)
+        Ok(lhs)
     }
 
-    fn parse_prefix_expr(&mut self) -> Option<Expr> {
+    fn parse_prefix_expr(&mut self) -> Result<Expr, CalcError> {
         match self.current_token() {
-            Some(token) => match *token {
+            Some(spanned) => match spanned.value {
                 Token::Plus => {
                     self.next();
                     let rhs = self.parse_expr(Precedence::Prefix)?;
-                    Some(Expr::PrefixExpr(PrefixOperator::Plus, Box::new(rhs)))
+                    Ok(Expr::PrefixExpr(PrefixOperator::Plus, Box::new(rhs)))
                 }
                 Token::Minus => {
                     self.next();
                     let rhs = self.parse_expr(Precedence::Prefix)?;
-                    Some(Expr::PrefixExpr(PrefixOperator::Minus, Box::new(rhs)))
+                    Ok(Expr::PrefixExpr(PrefixOperator::Minus, Box::new(rhs)))
                 }
-                Token::Num(num) => Some(Expr::UnaryExpr(num)),
+                Token::Num(num) => Ok(Expr::UnaryExpr(num)),
+                Token::Float(num) => Ok(Expr::FloatExpr(num)),
+                Token::Ident(ref name) => self.parse_ident_or_assign_expr(name.clone()),
                 Token::Lparen => self.parse_grouped_expr(),
-                _ => None,
+                _ => Err(CalcError::UnexpectedToken {
+                    token: spanned.value.clone(),
+                    column: spanned.column,
+                }),
             },
-            _ => None,
+            None => Err(CalcError::UnexpectedEof),
         }
     }
 
-    fn parse_infix_expr(&mut self, lhs: Expr) -> Option<Expr> {
-        Some(match self.current_token() {
-            Some(token) => match *token {
+    fn parse_infix_expr(&mut self, lhs: Expr) -> Result<Expr, CalcError> {
+        match self.current_token() {
+            Some(spanned) => match spanned.value {
                 Token::Plus => {
                     self.next();
                     let rhs = self.parse_expr(Precedence::Sum)?;
-                    Expr::BinaryExpr {
+                    Ok(Expr::BinaryExpr {
                         left: Box::new(lhs),
                         right: Box::new(rhs),
                         op: Operator::Add,
-                    }
+                    })
                 }
                 Token::Minus => {
                     self.next();
                     let rhs = self.parse_expr(Precedence::Sum)?;
-                    Expr::BinaryExpr {
+                    Ok(Expr::BinaryExpr {
                         left: Box::new(lhs),
                         right: Box::new(rhs),
                         op: Operator::Sub,
-                    }
+                    })
                 }
                 Token::Slash => {
                     self.next();
                     let rhs = self.parse_expr(Precedence::Product)?;
-                    Expr::BinaryExpr {
+                    Ok(Expr::BinaryExpr {
                         left: Box::new(lhs),
                         right: Box::new(rhs),
                         op: Operator::Div,
-                    }
+                    })
                 }
                 Token::Asterisk => {
                     self.next();
                     let rhs = self.parse_expr(Precedence::Product)?;
-                    Expr::BinaryExpr {
+                    Ok(Expr::BinaryExpr {
                         left: Box::new(lhs),
                         right: Box::new(rhs),
                         op: Operator::Mul,
-                    }
+                    })
                 }
                 Token::Percent => {
                     self.next();
                     let rhs = self.parse_expr(Precedence::Product)?;
-                    Expr::BinaryExpr {
+                    Ok(Expr::BinaryExpr {
                         left: Box::new(lhs),
                         right: Box::new(rhs),
                         op: Operator::Rem,
-                    }
+                    })
                 }
-
-                _ => return None,
+                Token::Circumflex => {
+                    self.next();
+                    // Recurse at Product (one below Exponent) so chained `^` binds right-to-left.
+                    let rhs = self.parse_expr(Precedence::Product)?;
+                    Ok(Expr::BinaryExpr {
+                        left: Box::new(lhs),
+                        right: Box::new(rhs),
+                        op: Operator::Pow,
+                    })
+                }
+                Token::Lt => self.parse_comparison_expr(lhs, Operator::Lt),
+                Token::Gt => self.parse_comparison_expr(lhs, Operator::Gt),
+                Token::Le => self.parse_comparison_expr(lhs, Operator::Le),
+                Token::Ge => self.parse_comparison_expr(lhs, Operator::Ge),
+                Token::EqEq => self.parse_comparison_expr(lhs, Operator::Eq),
+                Token::NotEq => self.parse_comparison_expr(lhs, Operator::NotEq),
+                Token::AmperAmper => self.parse_logical_expr(lhs, Operator::And),
+                Token::PipePipe => self.parse_logical_expr(lhs, Operator::Or),
+                Token::Amper => self.parse_bitand_expr(lhs),
+                Token::Pipe => self.parse_bitor_expr(lhs),
+                Token::Shl => self.parse_shift_expr(lhs, Operator::Shl),
+                Token::Shr => self.parse_shift_expr(lhs, Operator::Shr),
+                _ => Err(CalcError::UnexpectedToken {
+                    token: spanned.value.clone(),
+                    column: spanned.column,
+                }),
             },
-            _ => return None,
+            None => Err(CalcError::UnexpectedEof),
+        }
+    }
+
+    fn parse_comparison_expr(&mut self, lhs: Expr, op: Operator) -> Result<Expr, CalcError> {
+        self.next();
+        let rhs = self.parse_expr(Precedence::Comparison)?;
+        Ok(Expr::BinaryExpr {
+            left: Box::new(lhs),
+            right: Box::new(rhs),
+            op,
         })
     }
 
-    fn parse_postfix_expr(&mut self, lhs: Expr) -> Option<Expr> {
-        Some(match self.peek_token() {
-            Some(token) => match *token {
+    fn parse_logical_expr(&mut self, lhs: Expr, op: Operator) -> Result<Expr, CalcError> {
+        self.next();
+        let rhs = self.parse_expr(Precedence::Logical)?;
+        Ok(Expr::BinaryExpr {
+            left: Box::new(lhs),
+            right: Box::new(rhs),
+            op,
+        })
+    }
+
+    fn parse_bitor_expr(&mut self, lhs: Expr) -> Result<Expr, CalcError> {
+        self.next();
+        let rhs = self.parse_expr(Precedence::BitOr)?;
+        Ok(Expr::BinaryExpr {
+            left: Box::new(lhs),
+            right: Box::new(rhs),
+            op: Operator::BitOr,
+        })
+    }
+
+    fn parse_bitand_expr(&mut self, lhs: Expr) -> Result<Expr, CalcError> {
+        self.next();
+        let rhs = self.parse_expr(Precedence::BitAnd)?;
+        Ok(Expr::BinaryExpr {
+            left: Box::new(lhs),
+            right: Box::new(rhs),
+            op: Operator::BitAnd,
+        })
+    }
+
+    fn parse_shift_expr(&mut self, lhs: Expr, op: Operator) -> Result<Expr, CalcError> {
+        self.next();
+        let rhs = self.parse_expr(Precedence::Shift)?;
+        Ok(Expr::BinaryExpr {
+            left: Box::new(lhs),
+            right: Box::new(rhs),
+            op,
+        })
+    }
+
+    fn parse_postfix_expr(&mut self, lhs: Expr) -> Result<Expr, CalcError> {
+        match self.peek_token() {
+            Some(spanned) => match spanned.value {
                 Token::Exclamation => {
                     self.next();
-                    Expr::PostfixExpr(PostfixOperator::Factorial, Box::new(lhs))
-                }
-                Token::Circumflex => {
-                    self.next();
-                    self.next();
-
-                    if let Some(Token::Num(n)) = self.current_token() {
-                        Expr::PostfixExpr(
-                            PostfixOperator::Exponential(n.clone() as u32),
-                            Box::new(lhs),
-                        )
-                    } else {
-                        return None;
-                    }
+                    Ok(Expr::PostfixExpr(PostfixOperator::Factorial, Box::new(lhs)))
                 }
-                _ => lhs,
+                _ => Ok(lhs),
             },
-            _ => lhs,
-        })
+            None => Ok(lhs),
+        }
+    }
+
+    fn parse_ident_or_assign_expr(&mut self, name: String) -> Result<Expr, CalcError> {
+        if self.peek_token_is(&Token::Assign) {
+            self.next();
+            self.next();
+            let rhs = self.parse_expr(Precedence::Lowest)?;
+            Ok(Expr::Assign {
+                name,
+                expr: Box::new(rhs),
+            })
+        } else if self.peek_token_is(&Token::Lparen) {
+            self.next();
+            self.parse_call_expr(name)
+        } else {
+            Ok(Expr::Ident(name))
+        }
     }
 
-    fn parse_grouped_expr(&mut self) -> Option<Expr> {
+    fn parse_call_expr(&mut self, name: String) -> Result<Expr, CalcError> {
+        let mut args = Vec::new();
+
+        if self.peek_token_is(&Token::Rparen) {
+            self.next();
+            return Ok(Expr::Call { name, args });
+        }
+
+        loop {
+            self.next();
+            args.push(self.parse_expr(Precedence::Lowest)?);
+
+            if self.peek_token_is(&Token::Comma) {
+                self.next();
+            } else {
+                break;
+            }
+        }
+
+        if self.peek_token_is(&Token::Rparen) {
+            self.next();
+            Ok(Expr::Call { name, args })
+        } else {
+            match self.peek_token() {
+                Some(spanned) => Err(CalcError::UnexpectedToken {
+                    token: spanned.value.clone(),
+                    column: spanned.column,
+                }),
+                None => Err(CalcError::UnexpectedEof),
+            }
+        }
+    }
+
+    fn parse_grouped_expr(&mut self) -> Result<Expr, CalcError> {
         self.next();
-        let lhs = self.parse_expr(Precedence::Lowest);
+        let lhs = self.parse_expr(Precedence::Lowest)?;
 
         if self.peek_token_is(&Token::Rparen) {
             self.next();
-            lhs
+            Ok(lhs)
         } else {
-            None
+            match self.peek_token() {
+                Some(spanned) => Err(CalcError::UnexpectedToken {
+                    token: spanned.value.clone(),
+                    column: spanned.column,
+                }),
+                None => Err(CalcError::UnexpectedEof),
+            }
         }
     }
 
@@ -153,19 +295,27 @@ impl Parser {
         self.index += 1;
     }
 
-    fn current_token(&self) -> Option<&Token> {
+    fn current_token(&self) -> Option<&Spanned<Token>> {
         self.tokens.get(self.index)
     }
 
-    fn peek_token(&self) -> Option<&Token> {
+    fn peek_token(&self) -> Option<&Spanned<Token>> {
         self.tokens.get(self.index + 1)
     }
 
     fn peek_token_to_precedence(&self) -> Precedence {
         match self.peek_token() {
-            Some(token) => match *token {
+            Some(spanned) => match spanned.value {
                 Token::Minus | Token::Plus => Precedence::Sum,
                 Token::Slash | Token::Asterisk | Token::Percent => Precedence::Product,
+                Token::Circumflex => Precedence::Exponent,
+                Token::Shl | Token::Shr => Precedence::Shift,
+                Token::Lt | Token::Gt | Token::Le | Token::Ge | Token::EqEq | Token::NotEq => {
+                    Precedence::Comparison
+                }
+                Token::Amper => Precedence::BitAnd,
+                Token::Pipe => Precedence::BitOr,
+                Token::AmperAmper | Token::PipePipe => Precedence::Logical,
                 _ => Precedence::Lowest,
             },
             _ => Precedence::Lowest,
@@ -173,10 +323,9 @@ impl Parser {
     }
 
     fn peek_token_is(&self, token: &Token) -> bool {
-        if let Some(t) = self.peek_token() {
-            t == token
-        } else {
-            false
+        match self.peek_token() {
+            Some(spanned) => &spanned.value == token,
+            None => false,
         }
     }
 }
@@ -187,12 +336,12 @@ mod tests {
     use crate::lexer::Lexer;
     use crate::parser::Parser;
 
-    fn create_expr(line: &str) -> Option<Expr> {
+    fn create_expr(line: &str) -> Expr {
         let mut lexer = Lexer::new(line);
         let tokens = lexer.tokenize().unwrap();
 
         let mut parser = Parser::new(tokens);
-        parser.parse()
+        parser.parse().unwrap()
     }
 
     #[test]
@@ -200,7 +349,7 @@ mod tests {
         let expr = create_expr("1 + 2");
 
         assert_eq!(
-            expr.unwrap(),
+            expr,
             Expr::BinaryExpr {
                 left: Box::new(Expr::UnaryExpr(1)),
                 right: Box::new(Expr::UnaryExpr(2)),
@@ -214,7 +363,7 @@ mod tests {
         let expr = create_expr("(3 - 1) * -5");
 
         assert_eq!(
-            expr.unwrap(),
+            expr,
             Expr::BinaryExpr {
                 left: Box::new(Expr::BinaryExpr {
                     left: Box::new(Expr::UnaryExpr(3)),
@@ -235,7 +384,7 @@ mod tests {
         let expr = create_expr("(3 - 1) * (-(3 + 3) / -2)");
 
         assert_eq!(
-            expr.unwrap(),
+            expr,
             Expr::BinaryExpr {
                 left: Box::new(Expr::BinaryExpr {
                     left: Box::new(Expr::UnaryExpr(3)),
@@ -267,7 +416,7 @@ mod tests {
         let expr = create_expr("3! - 2!");
 
         assert_eq!(
-            expr.unwrap(),
+            expr,
             Expr::BinaryExpr {
                 left: Box::new(Expr::PostfixExpr(
                     PostfixOperator::Factorial,
@@ -287,7 +436,7 @@ mod tests {
         let expr = create_expr("(3 - 1) * (-(3 + 3) / -2)!");
 
         assert_eq!(
-            expr.unwrap(),
+            expr,
             Expr::BinaryExpr {
                 left: Box::new(Expr::BinaryExpr {
                     left: Box::new(Expr::UnaryExpr(3)),
@@ -322,15 +471,239 @@ mod tests {
         let expr = create_expr("3^2 - 2");
 
         assert_eq!(
-            expr.unwrap(),
+            expr,
             Expr::BinaryExpr {
-                left: Box::new(Expr::PostfixExpr(
-                    PostfixOperator::Exponential(2),
-                    Box::new(Expr::UnaryExpr(3)),
-                )),
+                left: Box::new(Expr::BinaryExpr {
+                    left: Box::new(Expr::UnaryExpr(3)),
+                    right: Box::new(Expr::UnaryExpr(2)),
+                    op: Operator::Pow
+                }),
                 right: Box::new(Expr::UnaryExpr(2)),
                 op: Operator::Sub
             }
         );
     }
+
+    #[test]
+    fn parse7_test() {
+        let mut lexer = Lexer::new("1 +");
+        let tokens = lexer.tokenize().unwrap();
+        let mut parser = Parser::new(tokens);
+
+        assert!(parser.parse().is_err());
+    }
+
+    #[test]
+    fn parse8_test() {
+        let expr = create_expr("2^3^2");
+
+        assert_eq!(
+            expr,
+            Expr::BinaryExpr {
+                left: Box::new(Expr::UnaryExpr(2)),
+                right: Box::new(Expr::BinaryExpr {
+                    left: Box::new(Expr::UnaryExpr(3)),
+                    right: Box::new(Expr::UnaryExpr(2)),
+                    op: Operator::Pow
+                }),
+                op: Operator::Pow
+            }
+        );
+    }
+
+    #[test]
+    fn parse9_test() {
+        let expr = create_expr("2^(1+1)");
+
+        assert_eq!(
+            expr,
+            Expr::BinaryExpr {
+                left: Box::new(Expr::UnaryExpr(2)),
+                right: Box::new(Expr::BinaryExpr {
+                    left: Box::new(Expr::UnaryExpr(1)),
+                    right: Box::new(Expr::UnaryExpr(1)),
+                    op: Operator::Add
+                }),
+                op: Operator::Pow
+            }
+        );
+    }
+
+    #[test]
+    fn parse10_test() {
+        let expr = create_expr("3 < 5");
+
+        assert_eq!(
+            expr,
+            Expr::BinaryExpr {
+                left: Box::new(Expr::UnaryExpr(3)),
+                right: Box::new(Expr::UnaryExpr(5)),
+                op: Operator::Lt
+            }
+        );
+    }
+
+    #[test]
+    fn parse11_test() {
+        let expr = create_expr("2 + 2 == 4");
+
+        assert_eq!(
+            expr,
+            Expr::BinaryExpr {
+                left: Box::new(Expr::BinaryExpr {
+                    left: Box::new(Expr::UnaryExpr(2)),
+                    right: Box::new(Expr::UnaryExpr(2)),
+                    op: Operator::Add
+                }),
+                right: Box::new(Expr::UnaryExpr(4)),
+                op: Operator::Eq
+            }
+        );
+    }
+
+    #[test]
+    fn parse12_test() {
+        let expr = create_expr("1 < 2 && 3 < 4");
+
+        assert_eq!(
+            expr,
+            Expr::BinaryExpr {
+                left: Box::new(Expr::BinaryExpr {
+                    left: Box::new(Expr::UnaryExpr(1)),
+                    right: Box::new(Expr::UnaryExpr(2)),
+                    op: Operator::Lt
+                }),
+                right: Box::new(Expr::BinaryExpr {
+                    left: Box::new(Expr::UnaryExpr(3)),
+                    right: Box::new(Expr::UnaryExpr(4)),
+                    op: Operator::Lt
+                }),
+                op: Operator::And
+            }
+        );
+    }
+
+    #[test]
+    fn parse13_test() {
+        let expr = create_expr("x = 1 + 2");
+
+        assert_eq!(
+            expr,
+            Expr::Assign {
+                name: "x".to_string(),
+                expr: Box::new(Expr::BinaryExpr {
+                    left: Box::new(Expr::UnaryExpr(1)),
+                    right: Box::new(Expr::UnaryExpr(2)),
+                    op: Operator::Add
+                })
+            }
+        );
+    }
+
+    #[test]
+    fn parse14_test() {
+        let expr = create_expr("x + 1");
+
+        assert_eq!(
+            expr,
+            Expr::BinaryExpr {
+                left: Box::new(Expr::Ident("x".to_string())),
+                right: Box::new(Expr::UnaryExpr(1)),
+                op: Operator::Add
+            }
+        );
+    }
+
+    #[test]
+    fn parse15_test() {
+        let expr = create_expr("gcd(12, 18)");
+
+        assert_eq!(
+            expr,
+            Expr::Call {
+                name: "gcd".to_string(),
+                args: vec![Expr::UnaryExpr(12), Expr::UnaryExpr(18)],
+            }
+        );
+    }
+
+    #[test]
+    fn parse16_test() {
+        let expr = create_expr("sqrt(2 + 2)");
+
+        assert_eq!(
+            expr,
+            Expr::Call {
+                name: "sqrt".to_string(),
+                args: vec![Expr::BinaryExpr {
+                    left: Box::new(Expr::UnaryExpr(2)),
+                    right: Box::new(Expr::UnaryExpr(2)),
+                    op: Operator::Add
+                }],
+            }
+        );
+    }
+
+    #[test]
+    fn parse17_test() {
+        let expr = create_expr("5 & 3 | 8");
+
+        assert_eq!(
+            expr,
+            Expr::BinaryExpr {
+                left: Box::new(Expr::BinaryExpr {
+                    left: Box::new(Expr::UnaryExpr(5)),
+                    right: Box::new(Expr::UnaryExpr(3)),
+                    op: Operator::BitAnd
+                }),
+                right: Box::new(Expr::UnaryExpr(8)),
+                op: Operator::BitOr
+            }
+        );
+    }
+
+    #[test]
+    fn parse18_test() {
+        let expr = create_expr("1 << 2 + 3");
+
+        assert_eq!(
+            expr,
+            Expr::BinaryExpr {
+                left: Box::new(Expr::UnaryExpr(1)),
+                right: Box::new(Expr::BinaryExpr {
+                    left: Box::new(Expr::UnaryExpr(2)),
+                    right: Box::new(Expr::UnaryExpr(3)),
+                    op: Operator::Add
+                }),
+                op: Operator::Shl
+            }
+        );
+    }
+
+    #[test]
+    fn parse19_test() {
+        let mut lexer = Lexer::new("1 2");
+        let tokens = lexer.tokenize().unwrap();
+        let mut parser = Parser::new(tokens);
+
+        assert!(parser.parse().is_err());
+    }
+
+    #[test]
+    fn parse20_test() {
+        let expr = create_expr("8 | 5 & 3");
+
+        assert_eq!(
+            expr,
+            Expr::BinaryExpr {
+                left: Box::new(Expr::UnaryExpr(8)),
+                right: Box::new(Expr::BinaryExpr {
+                    left: Box::new(Expr::UnaryExpr(5)),
+                    right: Box::new(Expr::UnaryExpr(3)),
+                    op: Operator::BitAnd
+                }),
+                op: Operator::BitOr
+            }
+        );
+    }
 }
@@ -1,6 +1,8 @@
 #[derive(Debug, Clone, PartialEq)]
 pub enum Token {
     Num(i64),
+    Float(f64),
+    Ident(String),
     Plus,
     Minus,
     Asterisk,
@@ -10,4 +12,24 @@ pub enum Token {
     Rparen,
     Exclamation,
     Circumflex,
+    Comma,
+    Assign,
+    Lt,
+    Gt,
+    Le,
+    Ge,
+    EqEq,
+    NotEq,
+    AmperAmper,
+    PipePipe,
+    Amper,
+    Pipe,
+    Shl,
+    Shr,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spanned<T> {
+    pub value: T,
+    pub column: usize,
 }